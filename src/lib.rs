@@ -42,6 +42,14 @@
 //! * **center**, has not effect, but it\"s accepted for completeness.
 //! * **framed**, add a gray frame to the diagram.
 //! * **transparent**, do not add the gray frame to the diagram.
+//! * **root**, resolve the path from the crate root instead of the calling file.
+//! * **theme(name)** or **theme(name, { ... })**, set the mermaid theme, optionally overriding
+//!   theme variables. See [Theme](#theme) below.
+//! * **init({ ... })**, pass a raw options object straight through to `mermaid.initialize`. See
+//!   [Raw init options](#raw-init-options) below.
+//! * **src("...")**, import mermaid.js from a custom URL instead of the jsdelivr default. See
+//!   [Pinning or self-hosting the mermaid.js source](#pinning-or-self-hosting-the-mermaidjs-source)
+//!   below.
 //!
 //! *Left*, *center* and *right* are, of course, mutually exclusive; but either can be combined with *framed*.
 //!
@@ -58,6 +66,81 @@
 #![doc = mermaid!("timeline.mmd" right)]
 #![doc = mermaid!("larger.mmd" center)]
 //!
+//! ## Theme
+//! Without any further input, the diagram follows rustdoc's own theme: it switches to mermaid's
+//! `dark` theme whenever the page theme is `dark` or `ayu`, and otherwise lets mermaid pick its
+//! own default. Add a `theme(<name>)` keyword to pin one of mermaid's themes (`default`, `forest`,
+//! `dark`, `neutral`, `base`, ...) regardless of the page theme, optionally followed by a
+//! `{ ... }` object with [themeVariables] overrides, valid only alongside the `base` theme:
+//!
+//! ```rust
+//! # use simple_mermaid::mermaid;
+//! #[doc = mermaid!("graph.mmd" theme(forest))]
+//! #[doc = mermaid!("graph.mmd" theme(base, { "primaryColor": "#ff0000" }) framed)]
+//! # fn function() {}
+//! ```
+#![doc = mermaid!("graph.mmd" theme(forest))]
+//!
+//! [themeVariables]: https://mermaid.js.org/config/theming.html#theme-variables
+//!
+//! ## Manifest-relative paths
+//! By default the diagram path is resolved the same way as [include_str], relative to the file
+//! where the [mermaid!] call is written. That breaks when a module embedding a diagram gets
+//! re-exported or moved around a workspace. Add the `root` keyword to resolve the path from
+//! `CARGO_MANIFEST_DIR` instead, so it stays stable no matter which module embeds it:
+//!
+//! ```rust
+//! # use simple_mermaid::mermaid;
+//! #[doc = mermaid!("src/graph.mmd" root)]
+//! # fn function() {}
+//! ```
+//!
+//! ## Raw init options
+//! For anything beyond theming — `securityLevel`, `flowchart`/`sequence` tweaks, `startOnLoad`,
+//! and so on — add an `init({ ... })` keyword with a raw object literal. It is spread into the
+//! same [mermaid.initialize] call *after* the `theme(...)` options, so both can be combined
+//! freely, and a `theme`/`themeVariables` key inside `init(...)` always wins over `theme(...)`
+//! and the dark/ayu auto-detection:
+//!
+//! ```rust
+//! # use simple_mermaid::mermaid;
+//! #[doc = mermaid!("graph.mmd" init({ "securityLevel": "loose", "startOnLoad": true }))]
+//! # fn function() {}
+//! ```
+//!
+//! [mermaid.initialize]: https://mermaid.js.org/config/setup/modules/mermaid.html
+//!
+//! # Mermaid version
+//! Diagrams are rendered with [mermaid] 11, pulled from [jsdelivr]. This unlocks the diagram types
+//! introduced after mermaid 10, such as `packet-beta` and `architecture`, with no extra setup:
+//!
+//! ```rust
+//! # use simple_mermaid::mermaid;
+//! #[doc = mermaid!("packet.mmd" center framed)]
+//! # fn function() {}
+//! ```
+#![doc = mermaid!("packet.mmd" center framed)]
+//!
+//! [jsdelivr]: https://www.jsdelivr.com/
+//!
+//! ## Pinning or self-hosting the mermaid.js source
+//! The `<script>` tag emitted for every diagram imports mermaid from the jsdelivr URL above by
+//! default. Add a `src("...")` keyword with your own URL to pin an exact mermaid release, or to
+//! point at a `mermaid.esm.min.mjs` vendored alongside your documentation so `cargo doc` keeps
+//! working offline or behind a restrictive Content-Security-Policy:
+//!
+//! ```rust
+//! # use simple_mermaid::mermaid;
+//! #[doc = mermaid!("graph.mmd" src("https://cdn.jsdelivr.net/npm/mermaid@11.4.1/dist/mermaid.esm.min.mjs"))]
+//! # fn function() {}
+//! ```
+//!
+//! `src(...)` is per [mermaid!] call, not crate-wide: for a fully offline or CSP-locked-down doc
+//! build, every `mermaid!` invocation in the crate needs its own `src(...)`, otherwise that
+//! diagram's `<script>` keeps importing from jsdelivr. There is no single crate-level switch;
+//! if you have many call sites, define your own wrapper macro that always passes `src(...)` for
+//! you.
+//!
 //! # Alternatives
 //! ## aquamarine
 //! The [aquamarine] introduces a procedural macro that converts regular code blocks marked with the
@@ -112,38 +195,127 @@
 /// Look at the crate level documentation for all the options.
 #[macro_export]
 macro_rules! mermaid {
-    ($file:literal)               => { $crate::_mermaid_inner!($file center transparent) };
-    ($file:literal left framed)   => { $crate::_mermaid_inner!($file left framed) };
-    ($file:literal framed left)   => { $crate::_mermaid_inner!($file left framed) };
-    ($file:literal center framed) => { $crate::_mermaid_inner!($file center framed) };
-    ($file:literal framed center) => { $crate::_mermaid_inner!($file center framed) };
-    ($file:literal right framed)  => { $crate::_mermaid_inner!($file right framed) };
-    ($file:literal framed right)  => { $crate::_mermaid_inner!($file right framed) };
-    ($file:literal framed)        => { $crate::_mermaid_inner!($file center framed) };
-    ($file:literal left)          => { $crate::_mermaid_inner!($file left transparent) };
-    ($file:literal right)         => { $crate::_mermaid_inner!($file right transparent) };
-    ($file:literal center)        => { $crate::_mermaid_inner!($file center transparent) };
+    ($file:literal $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos center, @style transparent, @theme none, @root file, @init none, @src default, $($opt)*)
+    };
+}
+
+/// Parses the keywords following the file path, one at a time, accumulating them into the state
+/// threaded through `@pos`, `@style`, `@theme`, `@root`, `@init` and `@src`, then hands everything
+/// off to `_mermaid_inner!`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _mermaid_parse {
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, left $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos left, @style $style, @theme $theme, @root $root, @init $init, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, right $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos right, @style $style, @theme $theme, @root $root, @init $init, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, center $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos center, @style $style, @theme $theme, @root $root, @init $init, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, framed $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos $pos, @style framed, @theme $theme, @root $root, @init $init, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, transparent $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos $pos, @style transparent, @theme $theme, @root $root, @init $init, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, theme($name:ident) $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos $pos, @style $style, @theme ($name), @root $root, @init $init, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, theme($name:ident, $vars:tt) $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos $pos, @style $style, @theme ($name, $vars), @root $root, @init $init, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, root $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos $pos, @style $style, @theme $theme, @root root, @init $init, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, init($cfg:tt) $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos $pos, @style $style, @theme $theme, @root $root, @init $cfg, @src $src, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt, src($url:literal) $($opt:tt)*) => {
+        $crate::_mermaid_parse!(@file $file, @pos $pos, @style $style, @theme $theme, @root $root, @init $init, @src $url, $($opt)*)
+    };
+    (@file $file:literal, @pos $pos:tt, @style $style:tt, @theme $theme:tt, @root $root:tt, @init $init:tt, @src $src:tt,) => {
+        $crate::_mermaid_inner!($file $pos $style $theme $root $init $src)
+    };
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _mermaid_inner {
-    ($file:literal $pos:ident $style:ident)  =>
+    ($file:literal $pos:tt $style:tt $theme:tt $root:tt $init:tt $src:tt)  =>
     {
         concat!("<pre class=\"mermaid\" style=\"text-align:", stringify!($pos), ";", $crate::_mermaid_background!($style), "\">\n",
-                    include_str!($file), "\n",
+                    include_str!($crate::_mermaid_path!($root, $file)), "\n",
                 "</pre>",
                 "<script type=\"module\">",
-                    "import mermaid from \"https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.esm.min.mjs\";",
-                    "var doc_theme = localStorage.getItem(\"rustdoc-theme\");",
-                    "if (doc_theme === \"dark\" || doc_theme === \"ayu\") mermaid.initialize({theme: \"dark\"});",
+                    "import mermaid from \"", $crate::_mermaid_source!($src), "\";",
+                    $crate::_mermaid_init_script!($theme, $init),
                 "</script>")
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _mermaid_source {
+    (default) => { "https://cdn.jsdelivr.net/npm/mermaid@11/dist/mermaid.esm.min.mjs" };
+    ($url:literal) => { $url };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _mermaid_path {
+    (file, $file:literal) => { $file };
+    (root, $file:literal) => { concat!(env!("CARGO_MANIFEST_DIR"), "/", $file) };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _mermaid_background {
     (framed) =>  { "" };
     (transparent) => { "background: transparent;" };
 }
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _mermaid_theme_obj {
+    (none) => {
+        "theme: (doc_theme === \"dark\" || doc_theme === \"ayu\") ? \"dark\" : undefined"
+    };
+    (($name:ident)) => {
+        concat!("theme: \"", stringify!($name), "\"")
+    };
+    (($name:ident, $vars:tt)) => {
+        concat!("theme: \"", stringify!($name), "\", themeVariables: ", stringify!($vars))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _mermaid_init_overlay {
+    (none) => { "" };
+    ($cfg:tt) => { concat!(", ...", stringify!($cfg)) };
+}
+
+/// Emits the mermaid init script. Plain diagrams (no `theme()`/`init()` keyword) keep calling
+/// `mermaid.initialize` conditionally, only for the dark/ayu rustdoc themes, same as before those
+/// keywords existed. Once either keyword is used, `mermaid.initialize` is called unconditionally
+/// with the merged options; the `init(...)` object is spread in *after* the theme fields, so any
+/// `theme`/`themeVariables` key it sets wins over `theme(...)` and the dark/ayu auto-detection.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _mermaid_init_script {
+    (none, none) => {
+        concat!(
+            "var doc_theme = localStorage.getItem(\"rustdoc-theme\");",
+            "if (doc_theme === \"dark\" || doc_theme === \"ayu\") mermaid.initialize({theme: \"dark\"});"
+        )
+    };
+    ($theme:tt, $init:tt) => {
+        concat!(
+            "var doc_theme = localStorage.getItem(\"rustdoc-theme\");",
+            "mermaid.initialize({", $crate::_mermaid_theme_obj!($theme), $crate::_mermaid_init_overlay!($init), "});"
+        )
+    };
+}